@@ -1,6 +1,5 @@
 use std::{
     io::{BufRead as _, BufReader},
-    os::unix::process::CommandExt,
     path::{Path, PathBuf},
     process::{Command, Stdio, exit},
 };
@@ -16,18 +15,16 @@ fn try_main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Args = argh::cargo_from_env();
 
     let target = build_target(&args)?;
-    run(&args, &target)?;
+    run(nnd(&args, &target))?;
 
     Ok(())
 }
 
-fn cargo(args: &Args, json: bool) -> Command {
+fn cargo(args: &Args) -> Command {
     let mut cmd = Command::new("cargo");
     cmd.arg("build");
+    cmd.arg("--message-format=json");
 
-    if json {
-        cmd.arg("--message-format=json");
-    }
     if let Some(package) = &args.package {
         cmd.arg(format!("--package={package}"));
     }
@@ -37,6 +34,12 @@ fn cargo(args: &Args, json: bool) -> Command {
     if let Some(example) = &args.example {
         cmd.arg(format!("--example={example}"));
     }
+    if args.release {
+        cmd.arg("--release");
+    }
+    if let Some(profile) = &args.profile {
+        cmd.arg(format!("--profile={profile}"));
+    }
     if args.tests {
         cmd.arg("--tests");
     }
@@ -61,8 +64,8 @@ fn cargo(args: &Args, json: bool) -> Command {
 }
 
 fn build_target(args: &Args) -> std::io::Result<PathBuf> {
-    eprintln!("{:?}", cargo(args, true));
-    let mut cmd = cargo(args, true).stdout(Stdio::piped()).spawn()?;
+    eprintln!("{:?}", cargo(args));
+    let mut cmd = cargo(args).stdout(Stdio::piped()).spawn()?;
 
     macro_rules! bail {
         ($($tt:tt)*) => {{
@@ -77,12 +80,14 @@ fn build_target(args: &Args) -> std::io::Result<PathBuf> {
         None => bail!("failed to capture stdout"),
     };
 
-    let mut target: Option<PathBuf> = None;
+    let mut targets: Vec<Executable> = Vec::new();
+    let mut diagnostics: Vec<String> = Vec::new();
 
     for line in BufReader::new(stdout).lines() {
         let line = line?;
         match serde_json::from_str(&line) {
             Ok(CargoMessage::CompilerArtifact {
+                target,
                 profile,
                 executable,
             }) => {
@@ -93,17 +98,27 @@ fn build_target(args: &Args) -> std::io::Result<PathBuf> {
                             debug = profile.debuginfo
                         );
                     }
-                    if target.is_some() {
-                        bail!("build produced more than one executable");
-                    }
-                    target = Some(executable);
+                    targets.push(Executable {
+                        name: target.name,
+                        path: executable,
+                    });
+                }
+            }
+            Ok(CargoMessage::CompilerMessage { message }) => {
+                if let Some(rendered) = message.rendered {
+                    diagnostics.push(rendered);
                 }
             }
             Ok(CargoMessage::BuildFinished { success }) => {
                 if !success {
-                    // run the build again to get error messages
+                    // the build already emitted every diagnostic as a
+                    // `compiler-message`; print them and bail instead of
+                    // re-running the whole build just to see the errors
                     cmd.kill()?;
-                    return Err(cargo(args, false).exec());
+                    for diagnostic in &diagnostics {
+                        eprint!("{diagnostic}");
+                    }
+                    exit(1);
                 }
             }
             Ok(CargoMessage::Unknown) => {}
@@ -111,29 +126,93 @@ fn build_target(args: &Args) -> std::io::Result<PathBuf> {
         };
     }
 
-    let Some(target) = target else {
-        bail!("cargo did not output a compiler-artifact message");
+    let target = match targets.len() {
+        0 => bail!("cargo did not output a compiler-artifact message"),
+        1 => targets.pop().unwrap().path,
+        _ => match select_target(args, &targets) {
+            Some(target) => target,
+            None => exit(1),
+        },
     };
 
     Ok(target)
 }
 
-fn nnd(args: &Args, target: &Path) -> Command {
-    let mut cmd = Command::new("nnd");
-
-    if let Some(Breakpoint { file, line }) = &args.breakpoint {
-        let file = match std::fs::canonicalize(Path::new(&file)) {
-            Ok(file) => file,
-            Err(err) => {
-                eprintln!("{err}");
-                exit(1);
+/// Pick one executable out of a multi-executable build.
+///
+/// If `--select <name>` is given it must match exactly one executable (by
+/// cargo's target name); an unmatched or ambiguous `--select` is a hard error.
+/// Without `--select`, the discovered executables are listed and the user is
+/// asked to choose one interactively.
+fn select_target(args: &Args, targets: &[Executable]) -> Option<PathBuf> {
+    if let Some(select) = &args.select {
+        let mut matches = targets.iter().filter(|target| &target.name == select);
+        match (matches.next(), matches.next()) {
+            (Some(target), None) => return Some(target.path.clone()),
+            (Some(_), Some(_)) => {
+                eprintln!("`--select {select}` matched more than one executable");
+                return None;
             }
-        };
+            (None, _) => {
+                eprintln!("`--select {select}` did not match any executable");
+                return None;
+            }
+        }
+    }
 
-        cmd.arg("--breakpoint");
-        cmd.arg(format!("{file}:{line}", file = file.display()));
+    eprintln!("build produced more than one executable:");
+    for (i, target) in targets.iter().enumerate() {
+        eprintln!("  [{i}] {} ({})", target.name, target.path.display());
+    }
+    eprint!("select one to debug [0-{}]: ", targets.len() - 1);
+    std::io::Write::flush(&mut std::io::stderr()).ok();
+
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input).is_err() {
+        return None;
+    }
+    match input.trim().parse::<usize>() {
+        Ok(i) if i < targets.len() => Some(targets[i].path.clone()),
+        _ => {
+            eprintln!("invalid selection {:?}", input.trim());
+            None
+        }
+    }
+}
+
+fn nnd(args: &Args, target: &Path) -> Command {
+    let mut cmd = if args.runner.trim().is_empty() {
+        Command::new("nnd")
     } else {
+        let mut parts = args.runner.split_whitespace();
+        // `--runner` is non-empty, so there is always at least one token.
+        let mut cmd = Command::new(parts.next().unwrap());
+        cmd.args(parts);
+        cmd.arg("nnd");
+        cmd
+    };
+
+    if args.breakpoint.is_empty() {
         cmd.arg("-s");
+    } else {
+        for breakpoint in &args.breakpoint {
+            cmd.arg("--breakpoint");
+            match breakpoint {
+                Breakpoint::FileLine { file, line } => {
+                    let file = match std::fs::canonicalize(Path::new(&file)) {
+                        Ok(file) => file,
+                        Err(err) => {
+                            eprintln!("{err}");
+                            exit(1);
+                        }
+                    };
+                    cmd.arg(format!("{file}:{line}", file = file.display()));
+                }
+                Breakpoint::Symbol(symbol) => {
+                    cmd.arg(symbol);
+                }
+            }
+        }
     }
     cmd.arg(target);
     cmd.args(&args.extra_args);
@@ -141,8 +220,22 @@ fn nnd(args: &Args, target: &Path) -> Command {
     cmd
 }
 
-fn run(args: &Args, target: &Path) -> std::io::Result<()> {
-    Err(nnd(args, target).exec())
+/// Run `cmd` and do not return unless it fails.
+///
+/// On Unix this `exec()`s, replacing the process image. On platforms without
+/// `exec`, `cmd` is spawned and waited on instead, and we exit with its status
+/// code once it finishes. The returned error describes a failure to launch
+/// `cmd` or, off Unix, to wait on it.
+#[cfg(unix)]
+fn run(mut cmd: Command) -> std::io::Result<()> {
+    use std::os::unix::process::CommandExt as _;
+    Err(cmd.exec())
+}
+
+#[cfg(not(unix))]
+fn run(mut cmd: Command) -> std::io::Result<()> {
+    let status = cmd.spawn()?.wait()?;
+    exit(status.code().unwrap_or(1));
 }
 
 /// Run a target built by cargo under `nnd`
@@ -160,6 +253,14 @@ struct Args {
     #[argh(option)]
     example: Option<String>,
 
+    /// build with the `release` profile
+    #[argh(switch)]
+    release: bool,
+
+    /// build with the specified profile
+    #[argh(option)]
+    profile: Option<String>,
+
     /// build and debug tests
     #[argh(switch)]
     tests: bool,
@@ -172,6 +273,10 @@ struct Args {
     #[argh(option)]
     bench: Option<String>,
 
+    /// when a build produces multiple executables, debug the one with this name
+    #[argh(option)]
+    select: Option<String>,
+
     /// comma-separated list of features to activate
     #[argh(option, short = 'F')]
     features: Vec<String>,
@@ -184,34 +289,40 @@ struct Args {
     #[argh(switch, long = "no-default-features")]
     no_default_features: bool,
 
-    /// set a breakpoint (`file:line`)
+    /// set a breakpoint (`file:line` or a function/symbol name)
     ///
-    /// if not set, defaults to breakpoint on `main`
+    /// may be passed multiple times; if not set, defaults to breakpoint on `main`
     #[argh(option, long = "breakpoint", short = 'b')]
-    breakpoint: Option<Breakpoint>,
+    breakpoint: Vec<Breakpoint>,
+
+    /// command prefix to wrap the `nnd` invocation (e.g. `sudo -E`)
+    #[argh(option, long = "runner", default = "String::new()")]
+    runner: String,
 
     /// extra arguments to pass to the built binary
     #[argh(positional)]
     extra_args: Vec<String>,
 }
 
-struct Breakpoint {
-    file: String,
-    line: usize,
+enum Breakpoint {
+    FileLine { file: String, line: usize },
+    Symbol(String),
 }
 
 impl argh::FromArgValue for Breakpoint {
     fn from_arg_value(value: &str) -> Result<Self, String> {
-        let Some((file, line)) = value.split_once(':') else {
-            return Err(format!("invalid breakpoint {value:?}, expected file:line"));
-        };
-
-        let file = file.to_owned();
-        let line = line
-            .parse()
-            .map_err(|err| format!("invalid breakpoint line \"{line}\": {err}"))?;
+        // `file:line` where `line` parses as a number; anything else (including
+        // `my_crate::handler`) is treated as a function/symbol name.
+        if let Some((file, line)) = value.split_once(':') {
+            if let Ok(line) = line.parse() {
+                return Ok(Self::FileLine {
+                    file: file.to_owned(),
+                    line,
+                });
+            }
+        }
 
-        Ok(Self { file, line })
+        Ok(Self::Symbol(value.to_owned()))
     }
 }
 
@@ -220,9 +331,12 @@ impl argh::FromArgValue for Breakpoint {
 enum CargoMessage {
     #[serde(rename = "compiler-artifact")]
     CompilerArtifact {
+        target: ArtifactTarget,
         profile: Profile,
         executable: Option<PathBuf>,
     },
+    #[serde(rename = "compiler-message")]
+    CompilerMessage { message: Diagnostic },
     #[serde(rename = "build-finished")]
     BuildFinished { success: bool },
 
@@ -230,6 +344,24 @@ enum CargoMessage {
     Unknown,
 }
 
+#[derive(serde::Deserialize)]
+struct Diagnostic {
+    /// the diagnostic as already rendered by the compiler; plain text without
+    /// ANSI colors, since the build uses `--message-format=json`
+    rendered: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ArtifactTarget {
+    name: String,
+}
+
+/// A built executable and cargo's clean name for it (no `-<hash>` suffix).
+struct Executable {
+    name: String,
+    path: PathBuf,
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct Profile {
     debuginfo: DebugInfo,